@@ -1,44 +1,199 @@
 //! Discord webhook service implementation using Rust and Actix-web.
 //!
 //! This service handles Discord interactions webhooks:
-//! - Validates Ed25519 signatures on incoming requests
+//! - Validates Ed25519 signatures on incoming requests and rejects replays
+//!   of an already-accepted signature within the freshness window
+//! - Registers its slash commands with Discord at startup
 //! - Responds to Ping (type=1) with Pong (type=1)
-//! - Responds to Slash commands (type=2) with Deferred (type=5)
-//! - Publishes sanitized slash command payloads to Pub/Sub
+//! - Responds to Slash commands (type=2) with an immediate message (type=4)
+//!   when a handler is registered for that command, falling back to a
+//!   Deferred response (type=5) otherwise
+//! - Publishes sanitized slash command payloads for commands without an
+//!   immediate handler, via a durable retry queue so a publish failure
+//!   doesn't lose the interaction. The broker behind that publish is a
+//!   pluggable `MessageSink` (Pub/Sub, Redis, file, or in-memory); production
+//!   Pub/Sub authenticates with a service account's cached OAuth2 token
+//! - Exposes Prometheus metrics at `GET /metrics`
+
+mod gcp_auth;
+mod metrics;
+mod queue;
+mod replay_cache;
+mod sink;
 
 use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
-use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use chrono::Utc;
 use ed25519_dalek::{Signature, VerifyingKey};
 use serde::Deserialize;
 use serde_json::{json, Value};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::collections::HashMap;
 use std::env;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Discord interaction type, as sent on the wire in the `type` field.
+///
+/// <https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-object-interaction-type>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize_repr)]
+#[repr(i64)]
+enum InteractionType {
+    Ping = 1,
+    ApplicationCommand = 2,
+    MessageComponent = 3,
+    ApplicationCommandAutocomplete = 4,
+    ModalSubmit = 5,
+}
+
+impl InteractionType {
+    /// Label used for the `discord_interactions_total` metric.
+    fn metric_label(&self) -> &'static str {
+        match self {
+            InteractionType::Ping => "ping",
+            InteractionType::ApplicationCommand => "application_command",
+            InteractionType::MessageComponent => "message_component",
+            InteractionType::ApplicationCommandAutocomplete => "autocomplete",
+            InteractionType::ModalSubmit => "modal_submit",
+        }
+    }
+}
+
+/// Discord interaction response type, sent back in the `type` field of our
+/// reply.
+///
+/// <https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-response-object-interaction-callback-type>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr)]
+#[repr(i64)]
+enum InteractionResponseType {
+    Pong = 1,
+    ChannelMessageWithSource = 4,
+    DeferredChannelMessageWithSource = 5,
+    DeferredUpdateMessage = 6,
+    UpdateMessage = 7,
+    ApplicationCommandAutocompleteResult = 8,
+}
+
+/// A slash command this service knows how to answer immediately.
+///
+/// `definition` is synced to Discord at startup via [`register_commands`];
+/// `handler` is invoked from [`handle_application_command`] to build a type-4
+/// response in place of the deferred+Pub/Sub fallback.
+struct CommandDefinition {
+    name: &'static str,
+    description: &'static str,
+    handler: fn(&Value) -> Value,
+}
+
+fn handle_ping_command(_interaction: &Value) -> Value {
+    json!({ "content": "Pong!" })
+}
+
+/// Commands registered with Discord and answered synchronously. Any command
+/// not listed here falls back to the deferred response + Pub/Sub publish.
+const COMMAND_DEFINITIONS: &[CommandDefinition] = &[CommandDefinition {
+    name: "ping",
+    description: "Check whether the bot is responsive",
+    handler: handle_ping_command,
+}];
+
+/// Builds the handler lookup table used by [`handle_application_command`].
+fn command_handler_registry() -> HashMap<&'static str, fn(&Value) -> Value> {
+    COMMAND_DEFINITIONS
+        .iter()
+        .map(|c| (c.name, c.handler))
+        .collect()
+}
+
+/// PUTs `COMMAND_DEFINITIONS` to Discord's REST API, registering them as
+/// global commands or, when `guild_id` is set, as guild-scoped commands for
+/// fast iteration.
+async fn register_commands(
+    http_client: &reqwest::Client,
+    application_id: &str,
+    bot_token: &str,
+    guild_id: Option<&str>,
+) -> Result<(), reqwest::Error> {
+    let url = match guild_id {
+        Some(guild_id) => format!(
+            "https://discord.com/api/v10/applications/{}/guilds/{}/commands",
+            application_id, guild_id
+        ),
+        None => format!(
+            "https://discord.com/api/v10/applications/{}/commands",
+            application_id
+        ),
+    };
 
-// Interaction types
-const INTERACTION_TYPE_PING: i64 = 1;
-const INTERACTION_TYPE_APPLICATION_COMMAND: i64 = 2;
+    let body: Vec<Value> = COMMAND_DEFINITIONS
+        .iter()
+        .map(|c| {
+            json!({
+                "name": c.name,
+                "description": c.description,
+                "type": 1,
+            })
+        })
+        .collect();
+
+    let resp = http_client
+        .put(&url)
+        .header("Authorization", format!("Bot {}", bot_token))
+        .json(&body)
+        .send()
+        .await?;
+
+    if resp.status().is_success() {
+        log::info!(
+            "Registered {} slash command(s) with Discord",
+            COMMAND_DEFINITIONS.len()
+        );
+    } else {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        log::error!("Command registration failed: HTTP {} - {}", status, body);
+    }
 
-// Response types
-const RESPONSE_TYPE_PONG: i64 = 1;
-const RESPONSE_TYPE_DEFERRED_CHANNEL_MESSAGE: i64 = 5;
+    Ok(())
+}
 
 /// Application state shared across handlers
 struct AppState {
     public_key: VerifyingKey,
-    pubsub_topic: Option<String>,
-    project_id: Option<String>,
-    pubsub_emulator_host: Option<String>,
     http_client: reqwest::Client,
+    command_handlers: HashMap<&'static str, fn(&Value) -> Value>,
+    job_store: Arc<dyn queue::JobStore>,
+    message_sink: Arc<dyn sink::MessageSink>,
+    metrics: metrics::Metrics,
+    replay_cache: replay_cache::ReplayCache,
 }
 
-/// Discord interaction request (partial, for type detection)
+/// Discord interaction request (partial, just enough to dispatch on type)
 #[derive(Deserialize)]
-#[allow(dead_code)]
 struct Interaction {
     #[serde(rename = "type")]
-    interaction_type: i64,
+    interaction_type: InteractionType,
+}
+
+/// Slash-command invocation payload (`data` field when `type` is
+/// [`InteractionType::ApplicationCommand`] or
+/// [`InteractionType::ApplicationCommandAutocomplete`]).
+#[derive(Debug, Deserialize)]
+struct ApplicationCommandData {
+    name: String,
+}
+
+/// Message-component interaction payload (`data` field when `type` is
+/// [`InteractionType::MessageComponent`]).
+#[derive(Debug, Deserialize)]
+struct MessageComponentData {
+    custom_id: String,
+}
+
+/// Modal submission payload (`data` field when `type` is
+/// [`InteractionType::ModalSubmit`]).
+#[derive(Debug, Deserialize)]
+struct ModalSubmitData {
+    custom_id: String,
 }
 
 /// Create a JSON error response
@@ -120,24 +275,11 @@ fn sanitize_interaction(interaction: &Value) -> Value {
     sanitized
 }
 
-/// Publish interaction to Pub/Sub emulator via REST API
-async fn publish_to_pubsub(state: &AppState, interaction: &Value) {
-    let (topic, project, emulator_host) = match (
-        &state.pubsub_topic,
-        &state.project_id,
-        &state.pubsub_emulator_host,
-    ) {
-        (Some(t), Some(p), Some(h)) => (t, p, h),
-        _ => return,
-    };
-
+/// Sanitizes the interaction and derives its Pub/Sub attributes, producing a
+/// job ready to hand to the [`queue::JobStore`].
+fn build_publish_job(interaction: &Value) -> queue::Job {
     let sanitized = sanitize_interaction(interaction);
 
-    // Base64 encode the JSON data
-    let json_str = serde_json::to_string(&sanitized).unwrap_or_default();
-    let base64_data = BASE64.encode(json_str.as_bytes());
-
-    // Build attributes
     let mut attributes = serde_json::Map::new();
     if let Some(id) = sanitized.get("id").and_then(|v| v.as_str()) {
         attributes.insert("interaction_id".to_string(), json!(id));
@@ -161,79 +303,128 @@ async fn publish_to_pubsub(state: &AppState, interaction: &Value) {
     }
     attributes.insert("timestamp".to_string(), json!(Utc::now().to_rfc3339()));
 
-    // Build Pub/Sub REST API request body
-    let request_body = json!({
-        "messages": [{
-            "data": base64_data,
-            "attributes": attributes
-        }]
-    });
+    queue::Job::new(sanitized, attributes)
+}
 
-    // URL: http://{emulator}/v1/projects/{project}/topics/{topic}:publish
-    let url = format!(
-        "http://{}/v1/projects/{}/topics/{}:publish",
-        emulator_host, project, topic
-    );
-
-    // Send POST request
-    match state
-        .http_client
-        .post(&url)
-        .json(&request_body)
-        .send()
+/// Attempts to publish a queued job via the configured [`sink::MessageSink`].
+/// Returns `true` on success so the caller knows not to reschedule it.
+async fn publish_job(state: &AppState, job: &queue::Job) -> bool {
+    let success = match state
+        .message_sink
+        .publish(job.payload.clone(), job.attributes.clone())
         .await
     {
-        Ok(resp) => {
-            if resp.status().is_success() {
-                log::info!("Published to Pub/Sub successfully");
-            } else {
-                let status = resp.status();
-                let body = resp.text().await.unwrap_or_default();
-                log::error!("Pub/Sub publish failed: HTTP {} - {}", status, body);
-            }
-        }
+        Ok(()) => true,
         Err(e) => {
-            log::error!("Pub/Sub publish failed: {}", e);
+            log::error!("Message sink publish failed: {}", e);
+            false
         }
-    }
+    };
+    state.metrics.record_publish_result(success);
+    success
 }
 
 /// Handle Ping interaction
 fn handle_ping() -> HttpResponse {
-    HttpResponse::Ok().json(json!({ "type": RESPONSE_TYPE_PONG }))
+    HttpResponse::Ok().json(json!({ "type": InteractionResponseType::Pong }))
 }
 
 /// Handle Application Command (slash command)
 async fn handle_application_command(state: &AppState, interaction: Value) -> HttpResponse {
-    // Spawn Pub/Sub publish in background
-    let state_topic = state.pubsub_topic.clone();
-    let state_project = state.project_id.clone();
-    let state_emulator = state.pubsub_emulator_host.clone();
-    let http_client = state.http_client.clone();
-
-    if state_topic.is_some() && state_project.is_some() && state_emulator.is_some() {
-        let interaction_clone = interaction.clone();
-        tokio::spawn(async move {
-            let temp_state = AppState {
-                public_key: VerifyingKey::from_bytes(&[0u8; 32]).unwrap(), // Dummy, not used
-                pubsub_topic: state_topic,
-                project_id: state_project,
-                pubsub_emulator_host: state_emulator,
-                http_client,
-            };
-            publish_to_pubsub(&temp_state, &interaction_clone).await;
-        });
+    // Commands with a registered handler are answered immediately and never
+    // touch the Pub/Sub fallback.
+    let command_name = interaction
+        .get("data")
+        .and_then(|d| serde_json::from_value::<ApplicationCommandData>(d.clone()).ok())
+        .map(|d| d.name);
+
+    if let Some(handler) = command_name
+        .as_deref()
+        .and_then(|name| state.command_handlers.get(name))
+    {
+        let data = handler(&interaction);
+        return HttpResponse::Ok().json(json!({
+            "type": InteractionResponseType::ChannelMessageWithSource,
+            "data": data,
+        }));
     }
 
+    // Enqueue the publish rather than firing it off detached, so a broker
+    // hiccup gets retried instead of silently dropping the interaction. The
+    // configured MessageSink decides where (or whether) this actually goes.
+    state
+        .job_store
+        .enqueue(build_publish_job(&interaction))
+        .await;
+
     // Respond with deferred response (non-ephemeral)
-    HttpResponse::Ok().json(json!({ "type": RESPONSE_TYPE_DEFERRED_CHANNEL_MESSAGE }))
+    HttpResponse::Ok().json(json!({ "type": InteractionResponseType::DeferredChannelMessageWithSource }))
+}
+
+/// Handle a message-component interaction (buttons, select menus).
+///
+/// There's no component-specific business logic yet; acknowledge with a
+/// deferred update so Discord doesn't report the interaction as failed.
+fn handle_message_component(interaction: Value) -> HttpResponse {
+    if let Some(data) = interaction
+        .get("data")
+        .and_then(|d| serde_json::from_value::<MessageComponentData>(d.clone()).ok())
+    {
+        log::info!("Message component interaction: custom_id={}", data.custom_id);
+    }
+
+    HttpResponse::Ok().json(json!({ "type": InteractionResponseType::DeferredUpdateMessage }))
 }
 
-/// Main interaction handler
+/// Handle a modal submission.
+fn handle_modal_submit(interaction: Value) -> HttpResponse {
+    if let Some(data) = interaction
+        .get("data")
+        .and_then(|d| serde_json::from_value::<ModalSubmitData>(d.clone()).ok())
+    {
+        log::info!("Modal submit interaction: custom_id={}", data.custom_id);
+    }
+
+    HttpResponse::Ok().json(json!({ "type": InteractionResponseType::UpdateMessage }))
+}
+
+/// Handle an autocomplete request for a slash command option.
+///
+/// No commands expose dynamic choices yet, so this always returns an empty
+/// suggestion list rather than rejecting the interaction.
+fn handle_autocomplete(interaction: Value) -> HttpResponse {
+    let command_name = interaction
+        .get("data")
+        .and_then(|d| serde_json::from_value::<ApplicationCommandData>(d.clone()).ok())
+        .map(|d| d.name)
+        .unwrap_or_default();
+    log::info!("Autocomplete request for command: {}", command_name);
+
+    HttpResponse::Ok().json(json!({
+        "type": InteractionResponseType::ApplicationCommandAutocompleteResult,
+        "data": { "choices": Vec::<Value>::new() },
+    }))
+}
+
+/// Main interaction handler; times the request and delegates to
+/// [`handle_interaction_inner`] for the actual signature/JSON/type handling.
 async fn handle_interaction(
     req: HttpRequest,
     body: web::Bytes,
     state: web::Data<Arc<AppState>>,
+) -> HttpResponse {
+    let start = std::time::Instant::now();
+    let response = handle_interaction_inner(req, body, &state).await;
+    state
+        .metrics
+        .observe_handle_interaction(start.elapsed().as_secs_f64());
+    response
+}
+
+async fn handle_interaction_inner(
+    req: HttpRequest,
+    body: web::Bytes,
+    state: &AppState,
 ) -> HttpResponse {
     // Get signature headers
     let signature = req
@@ -255,33 +446,55 @@ async fn handle_interaction(
 
     // Validate signature
     if !validate_signature(&state.public_key, signature, timestamp, body_str) {
+        state.metrics.record_signature_failure();
         return error_response(401, "invalid signature");
     }
 
+    // Reject a replay of a signature we've already accepted within the
+    // freshness window, even though it's individually valid.
+    if !state.replay_cache.check_and_record(signature).await {
+        state.metrics.record_replay_rejection();
+        return error_response(401, "replayed request");
+    }
+
     // Parse JSON
     let interaction: Value = match serde_json::from_str(body_str) {
         Ok(v) => v,
-        Err(_) => return error_response(400, "invalid JSON"),
+        Err(_) => {
+            state.metrics.record_json_parse_failure();
+            return error_response(400, "invalid JSON");
+        }
     };
 
     // Ensure interaction is an object (not null, array, or primitive)
     if !interaction.is_object() {
+        state.metrics.record_json_parse_failure();
         return error_response(400, "invalid JSON");
     }
 
     // Get interaction type
-    let interaction_type = match interaction.get("type").and_then(|t| t.as_i64()) {
-        Some(t) => t,
-        None => return error_response(400, "unsupported interaction type"),
+    let typed: Interaction = match serde_json::from_value(interaction.clone()) {
+        Ok(t) => t,
+        Err(_) => {
+            state.metrics.record_json_parse_failure();
+            return error_response(400, "unsupported interaction type");
+        }
     };
 
-    // Handle by type
-    match interaction_type {
-        INTERACTION_TYPE_PING => handle_ping(),
-        INTERACTION_TYPE_APPLICATION_COMMAND => {
-            handle_application_command(&state, interaction).await
+    state
+        .metrics
+        .record_interaction(typed.interaction_type.metric_label());
+
+    // Handle by type. This match is exhaustive over `InteractionType`, so a
+    // new variant added to the enum fails to compile here until handled.
+    match typed.interaction_type {
+        InteractionType::Ping => handle_ping(),
+        InteractionType::ApplicationCommand => {
+            handle_application_command(state, interaction).await
         }
-        _ => error_response(400, "unsupported interaction type"),
+        InteractionType::MessageComponent => handle_message_component(interaction),
+        InteractionType::ApplicationCommandAutocomplete => handle_autocomplete(interaction),
+        InteractionType::ModalSubmit => handle_modal_submit(interaction),
     }
 }
 
@@ -290,6 +503,13 @@ async fn health_check() -> HttpResponse {
     HttpResponse::Ok().json(json!({ "status": "ok" }))
 }
 
+/// Prometheus scrape endpoint
+async fn metrics_handler(state: web::Data<Arc<AppState>>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(state.metrics.render())
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     env_logger::init();
@@ -300,6 +520,12 @@ async fn main() -> std::io::Result<()> {
         .and_then(|p| p.parse().ok())
         .unwrap_or(8080);
 
+    // How long an accepted signature is remembered for replay rejection.
+    let replay_window_secs: u64 = env::var("REPLAY_WINDOW_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
+
     let public_key_hex = env::var("DISCORD_PUBLIC_KEY")
         .expect("DISCORD_PUBLIC_KEY environment variable is required");
 
@@ -329,20 +555,106 @@ async fn main() -> std::io::Result<()> {
         );
     }
 
+    // Register slash commands with Discord, if credentials are configured.
+    if let (Ok(application_id), Ok(bot_token)) = (
+        env::var("DISCORD_APPLICATION_ID"),
+        env::var("DISCORD_BOT_TOKEN"),
+    ) {
+        let guild_id = env::var("DISCORD_GUILD_ID").ok();
+        if let Err(e) =
+            register_commands(&http_client, &application_id, &bot_token, guild_id.as_deref())
+                .await
+        {
+            log::error!("Failed to register slash commands: {}", e);
+        }
+    } else {
+        log::info!(
+            "DISCORD_APPLICATION_ID/DISCORD_BOT_TOKEN not set, skipping command registration"
+        );
+    }
+
+    // The publish retry queue: file-backed when a path is configured (so
+    // pending jobs survive a restart), in-memory otherwise.
+    let job_store: Arc<dyn queue::JobStore> = match env::var("JOB_QUEUE_PATH") {
+        Ok(path) => Arc::new(
+            queue::FileJobStore::new(path.into())
+                .expect("failed to open JOB_QUEUE_PATH for the publish retry queue"),
+        ),
+        Err(_) => Arc::new(queue::InMemoryJobStore::new()),
+    };
+
+    // The message-broker backend jobs are actually published to, selected
+    // via MESSAGE_SINK so the service is testable without a real broker.
+    let message_sink: Arc<dyn sink::MessageSink> =
+        match env::var("MESSAGE_SINK").as_deref().unwrap_or("pubsub") {
+            "memory" => Arc::new(sink::InMemorySink::new()),
+            "file" => {
+                let path = env::var("MESSAGE_SINK_FILE_PATH")
+                    .unwrap_or_else(|_| "messages.ndjson".to_string());
+                Arc::new(sink::FileSink::new(path.into()))
+            }
+            "redis" => {
+                let redis_url = env::var("REDIS_URL")
+                    .expect("REDIS_URL is required when MESSAGE_SINK=redis");
+                let stream_key =
+                    env::var("REDIS_STREAM").unwrap_or_else(|_| "discord-interactions".to_string());
+                Arc::new(
+                    sink::RedisSink::new(&redis_url, stream_key)
+                        .await
+                        .expect("failed to connect to Redis for MESSAGE_SINK=redis"),
+                )
+            }
+            _ => {
+                // No emulator means production Pub/Sub, which needs a
+                // service account to authenticate.
+                let auth = if pubsub_emulator_host.is_none() {
+                    env::var("GOOGLE_APPLICATION_CREDENTIALS").ok().map(|path| {
+                        let key = gcp_auth::ServiceAccountKey::from_file(std::path::Path::new(&path))
+                            .expect("failed to read GOOGLE_APPLICATION_CREDENTIALS key file");
+                        gcp_auth::ServiceAccountAuth::new(key, sink::PUBSUB_SCOPE)
+                            .expect("failed to build Pub/Sub service account client")
+                    })
+                } else {
+                    None
+                };
+
+                Arc::new(sink::PubSubSink::new(
+                    http_client.clone(),
+                    project_id,
+                    pubsub_topic,
+                    pubsub_emulator_host,
+                    auth,
+                ))
+            }
+        };
+
     let state = Arc::new(AppState {
         public_key,
-        pubsub_topic,
-        project_id,
-        pubsub_emulator_host,
         http_client,
+        command_handlers: command_handler_registry(),
+        job_store: job_store.clone(),
+        message_sink,
+        metrics: metrics::Metrics::new(),
+        replay_cache: replay_cache::ReplayCache::new(Duration::from_secs(replay_window_secs)),
     });
 
+    let worker_state = state.clone();
+    tokio::spawn(queue::run_worker(
+        job_store,
+        Duration::from_secs(1),
+        move |job| {
+            let worker_state = worker_state.clone();
+            async move { publish_job(&worker_state, &job).await }
+        },
+    ));
+
     println!("Starting server on port {}", port);
 
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(state.clone()))
             .route("/health", web::get().to(health_check))
+            .route("/metrics", web::get().to(metrics_handler))
             .route("/", web::post().to(handle_interaction))
             .route("/interactions", web::post().to(handle_interaction))
     })
@@ -350,3 +662,106 @@ async fn main() -> std::io::Result<()> {
     .run()
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    /// Builds an `AppState` wired to in-memory stores plus a deterministic
+    /// signing key, and returns the key so callers can sign test requests.
+    fn test_state() -> (Arc<AppState>, SigningKey, Arc<sink::InMemorySink>) {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let sink = Arc::new(sink::InMemorySink::new());
+
+        let state = Arc::new(AppState {
+            public_key: signing_key.verifying_key(),
+            http_client: reqwest::Client::new(),
+            command_handlers: command_handler_registry(),
+            job_store: Arc::new(queue::InMemoryJobStore::new()),
+            message_sink: sink.clone(),
+            metrics: metrics::Metrics::new(),
+            replay_cache: replay_cache::ReplayCache::new(Duration::from_secs(5)),
+        });
+
+        (state, signing_key, sink)
+    }
+
+    /// Signs `body` and returns the header values a genuine Discord request
+    /// would carry, so callers can build one or more `HttpRequest`s from them.
+    fn sign(signing_key: &SigningKey, body: &str) -> (String, String) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .to_string();
+        let message = format!("{}{}", timestamp, body);
+        let signature = signing_key.sign(message.as_bytes());
+        (hex::encode(signature.to_bytes()), timestamp)
+    }
+
+    /// Builds a signed `POST /interactions` request for `body`.
+    fn signed_request(signing_key: &SigningKey, body: &str) -> (HttpRequest, web::Bytes) {
+        let (signature, timestamp) = sign(signing_key, body);
+        (request_with_headers(&signature, &timestamp), web::Bytes::from(body.to_string()))
+    }
+
+    fn request_with_headers(signature: &str, timestamp: &str) -> HttpRequest {
+        actix_web::test::TestRequest::post()
+            .insert_header(("X-Signature-Ed25519", signature.to_string()))
+            .insert_header(("X-Signature-Timestamp", timestamp.to_string()))
+            .to_http_request()
+    }
+
+    #[actix_web::test]
+    async fn application_command_without_handler_is_queued_and_publishes() {
+        let (state, signing_key, sink) = test_state();
+
+        let body = json!({
+            "type": 2,
+            "id": "interaction-1",
+            "data": { "name": "not-a-registered-command" },
+        })
+        .to_string();
+        let (req, bytes) = signed_request(&signing_key, &body);
+
+        let response = handle_interaction_inner(req, bytes, &state).await;
+        assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+
+        let due = state.job_store.take_due().await;
+        assert_eq!(due.len(), 1);
+        assert!(publish_job(&state, &due[0]).await);
+
+        let published = sink.published();
+        assert_eq!(published.len(), 1);
+        assert_eq!(
+            published[0].1.get("command_name").and_then(Value::as_str),
+            Some("not-a-registered-command")
+        );
+    }
+
+    #[actix_web::test]
+    async fn replayed_signature_is_rejected() {
+        let (state, signing_key, _sink) = test_state();
+        let body = json!({ "type": 1 }).to_string();
+        let (signature, timestamp) = sign(&signing_key, &body);
+
+        let first = handle_interaction_inner(
+            request_with_headers(&signature, &timestamp),
+            web::Bytes::from(body.clone()),
+            &state,
+        )
+        .await;
+        assert_eq!(first.status(), actix_web::http::StatusCode::OK);
+
+        // Same signature and timestamp again: a captured replay, not a
+        // second independently valid request.
+        let replay = handle_interaction_inner(
+            request_with_headers(&signature, &timestamp),
+            web::Bytes::from(body),
+            &state,
+        )
+        .await;
+        assert_eq!(replay.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,138 @@
+//! Prometheus metrics for operational visibility.
+//!
+//! Registers counters and a latency histogram and renders them in Prometheus
+//! text exposition format for `GET /metrics`, so operators can alert on a
+//! spike in signature failures or publish errors without scraping logs.
+
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+
+pub struct Metrics {
+    registry: Registry,
+    interactions_total: IntCounterVec,
+    signature_failures_total: IntCounter,
+    replay_rejections_total: IntCounter,
+    json_parse_failures_total: IntCounter,
+    publish_results_total: IntCounterVec,
+    handle_interaction_duration_seconds: Histogram,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let interactions_total = IntCounterVec::new(
+            Opts::new(
+                "discord_interactions_total",
+                "Total interactions received, labeled by interaction type",
+            ),
+            &["type"],
+        )
+        .expect("valid metric definition");
+        registry
+            .register(Box::new(interactions_total.clone()))
+            .expect("metric not already registered");
+
+        let signature_failures_total = IntCounter::new(
+            "discord_signature_validation_failures_total",
+            "Total requests rejected for failing Ed25519 signature validation",
+        )
+        .expect("valid metric definition");
+        registry
+            .register(Box::new(signature_failures_total.clone()))
+            .expect("metric not already registered");
+
+        let replay_rejections_total = IntCounter::new(
+            "discord_replay_rejections_total",
+            "Total requests rejected as a replay of a previously accepted signature",
+        )
+        .expect("valid metric definition");
+        registry
+            .register(Box::new(replay_rejections_total.clone()))
+            .expect("metric not already registered");
+
+        let json_parse_failures_total = IntCounter::new(
+            "discord_json_parse_failures_total",
+            "Total requests rejected for an invalid or unsupported JSON body",
+        )
+        .expect("valid metric definition");
+        registry
+            .register(Box::new(json_parse_failures_total.clone()))
+            .expect("metric not already registered");
+
+        let publish_results_total = IntCounterVec::new(
+            Opts::new(
+                "discord_publish_results_total",
+                "Total message sink publish attempts, labeled by result",
+            ),
+            &["result"],
+        )
+        .expect("valid metric definition");
+        registry
+            .register(Box::new(publish_results_total.clone()))
+            .expect("metric not already registered");
+
+        let handle_interaction_duration_seconds = Histogram::with_opts(HistogramOpts::new(
+            "discord_handle_interaction_duration_seconds",
+            "Latency of handle_interaction from request to response",
+        ))
+        .expect("valid metric definition");
+        registry
+            .register(Box::new(handle_interaction_duration_seconds.clone()))
+            .expect("metric not already registered");
+
+        Self {
+            registry,
+            interactions_total,
+            signature_failures_total,
+            replay_rejections_total,
+            json_parse_failures_total,
+            publish_results_total,
+            handle_interaction_duration_seconds,
+        }
+    }
+
+    pub fn record_interaction(&self, interaction_type: &str) {
+        self.interactions_total
+            .with_label_values(&[interaction_type])
+            .inc();
+    }
+
+    pub fn record_signature_failure(&self) {
+        self.signature_failures_total.inc();
+    }
+
+    pub fn record_replay_rejection(&self) {
+        self.replay_rejections_total.inc();
+    }
+
+    pub fn record_json_parse_failure(&self) {
+        self.json_parse_failures_total.inc();
+    }
+
+    pub fn record_publish_result(&self, success: bool) {
+        let result = if success { "success" } else { "failure" };
+        self.publish_results_total.with_label_values(&[result]).inc();
+    }
+
+    pub fn observe_handle_interaction(&self, seconds: f64) {
+        self.handle_interaction_duration_seconds.observe(seconds);
+    }
+
+    /// Renders all registered metrics in Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let metric_families = self.registry.gather();
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&metric_families, &mut buffer)
+            .unwrap_or_default();
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,321 @@
+//! Durable retry queue for outbound publishes.
+//!
+//! A downstream sink (Pub/Sub, today) can be briefly unavailable. Rather than
+//! losing the interaction when a publish fails, the job is persisted here and
+//! retried with exponential backoff until it succeeds or exhausts its
+//! attempts, at which point it is dropped (dead-lettered).
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Maximum number of attempts before a job is dropped.
+const MAX_ATTEMPTS: u32 = 6;
+
+/// Base delay for exponential backoff, doubled per attempt and capped.
+const BASE_BACKOFF_SECS: u64 = 1;
+const MAX_BACKOFF_SECS: u64 = 60;
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A single unit of work: a sanitized interaction payload pending publish.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub payload: Value,
+    pub attributes: Map<String, Value>,
+    pub attempts: u32,
+    pub next_visible_at: u64,
+}
+
+impl Job {
+    /// Creates a job that is immediately due.
+    pub fn new(payload: Value, attributes: Map<String, Value>) -> Self {
+        Self {
+            payload,
+            attributes,
+            attempts: 0,
+            next_visible_at: now_secs(),
+        }
+    }
+
+    /// Schedules the next attempt after an exponential backoff, returning
+    /// `None` once [`MAX_ATTEMPTS`] is exceeded (the job should be dropped).
+    fn reschedule(mut self) -> Option<Job> {
+        self.attempts += 1;
+        if self.attempts >= MAX_ATTEMPTS {
+            return None;
+        }
+        let backoff = BASE_BACKOFF_SECS
+            .saturating_mul(1u64 << self.attempts.min(6))
+            .min(MAX_BACKOFF_SECS);
+        self.next_visible_at = now_secs() + backoff;
+        Some(self)
+    }
+}
+
+/// A backing store for pending jobs. Implementations must be safe to share
+/// across the async worker and request handlers.
+#[async_trait::async_trait]
+pub trait JobStore: Send + Sync {
+    /// Adds a job to the queue.
+    async fn enqueue(&self, job: Job);
+
+    /// Removes and returns every job whose `next_visible_at` has passed.
+    async fn take_due(&self) -> Vec<Job>;
+
+    /// Re-adds a job that failed an attempt (already rescheduled).
+    async fn requeue(&self, job: Job);
+}
+
+/// Non-durable store for tests and local development; lost on restart.
+#[derive(Default)]
+pub struct InMemoryJobStore {
+    jobs: Mutex<Vec<Job>>,
+}
+
+impl InMemoryJobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl JobStore for InMemoryJobStore {
+    async fn enqueue(&self, job: Job) {
+        self.jobs.lock().unwrap().push(job);
+    }
+
+    async fn take_due(&self) -> Vec<Job> {
+        let now = now_secs();
+        let mut jobs = self.jobs.lock().unwrap();
+        let (due, pending): (Vec<Job>, Vec<Job>) =
+            jobs.drain(..).partition(|j| j.next_visible_at <= now);
+        *jobs = pending;
+        due
+    }
+
+    async fn requeue(&self, job: Job) {
+        self.jobs.lock().unwrap().push(job);
+    }
+}
+
+/// NDJSON file-backed store so pending jobs survive a service restart.
+pub struct FileJobStore {
+    path: PathBuf,
+    jobs: Mutex<Vec<Job>>,
+}
+
+impl FileJobStore {
+    pub fn new(path: PathBuf) -> std::io::Result<Self> {
+        let jobs = if path.exists() {
+            std::fs::read_to_string(&path)?
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            path,
+            jobs: Mutex::new(jobs),
+        })
+    }
+
+    /// Offloads the write to a blocking-pool thread and awaits it, so a slow
+    /// disk stalls this job's own enqueue/requeue/take_due call rather than
+    /// the tokio worker thread serving every other in-flight request.
+    async fn persist(&self, jobs: Vec<Job>) {
+        let path = self.path.clone();
+        if let Err(e) = tokio::task::spawn_blocking(move || write_ndjson_atomic(&path, &jobs)).await
+        {
+            log::error!("Job queue persistence task panicked: {}", e);
+        }
+    }
+}
+
+/// Writes `jobs` to a temp file in the same directory as `path` and renames
+/// it over `path`, so a crash mid-write can't truncate or corrupt the
+/// on-disk queue (`rename` is atomic on the same filesystem). Synchronous;
+/// callers on an async executor should run this via `spawn_blocking`.
+fn write_ndjson_atomic(path: &std::path::Path, jobs: &[Job]) {
+    let ndjson = jobs
+        .iter()
+        .filter_map(|j| serde_json::to_string(j).ok())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let tmp_path = path.with_extension("tmp");
+    if let Err(e) = std::fs::write(&tmp_path, ndjson) {
+        log::error!(
+            "Failed to write job queue temp file {}: {}",
+            tmp_path.display(),
+            e
+        );
+        return;
+    }
+    if let Err(e) = std::fs::rename(&tmp_path, path) {
+        log::error!("Failed to persist job queue to {}: {}", path.display(), e);
+    }
+}
+
+#[async_trait::async_trait]
+impl JobStore for FileJobStore {
+    async fn enqueue(&self, job: Job) {
+        let snapshot = {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.push(job);
+            jobs.clone()
+        };
+        self.persist(snapshot).await;
+    }
+
+    async fn take_due(&self) -> Vec<Job> {
+        let now = now_secs();
+        let (due, snapshot) = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let (due, pending): (Vec<Job>, Vec<Job>) =
+                jobs.drain(..).partition(|j| j.next_visible_at <= now);
+            *jobs = pending;
+            (due, jobs.clone())
+        };
+        self.persist(snapshot).await;
+        due
+    }
+
+    async fn requeue(&self, job: Job) {
+        let snapshot = {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.push(job);
+            jobs.clone()
+        };
+        self.persist(snapshot).await;
+    }
+}
+
+/// Polls `store` for due jobs and attempts each via `publish`, rescheduling
+/// failures with backoff or dropping them once attempts are exhausted.
+/// Runs until the process exits; intended to be spawned as a background task.
+pub async fn run_worker<F, Fut>(store: Arc<dyn JobStore>, poll_interval: Duration, publish: F)
+where
+    F: Fn(Job) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = bool> + Send,
+{
+    loop {
+        tokio::time::sleep(poll_interval).await;
+        for job in store.take_due().await {
+            if publish(job.clone()).await {
+                continue;
+            }
+            let command_name = job
+                .attributes
+                .get("command_name")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            let interaction_id = job
+                .payload
+                .get("id")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            match job.reschedule() {
+                Some(rescheduled) => store.requeue(rescheduled).await,
+                None => log::error!(
+                    "Dropping job after {} failed attempts (dead-lettered): command={} interaction_id={}",
+                    MAX_ATTEMPTS,
+                    command_name,
+                    interaction_id
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    fn test_job() -> Job {
+        Job::new(json!({ "id": "interaction-1" }), serde_json::Map::new())
+    }
+
+    #[test]
+    fn reschedule_backs_off_exponentially_until_dead_lettered() {
+        let job = test_job();
+        let before = now_secs();
+
+        let job = job.reschedule().expect("attempt 1 of 6 should reschedule");
+        assert_eq!(job.attempts, 1);
+        assert!(job.next_visible_at >= before + 2);
+
+        let job = job.reschedule().expect("attempt 2 of 6 should reschedule");
+        assert_eq!(job.attempts, 2);
+        assert!(job.next_visible_at >= before + 4);
+
+        let job = job.reschedule().expect("attempt 3 of 6 should reschedule");
+        let job = job.reschedule().expect("attempt 4 of 6 should reschedule");
+        let job = job.reschedule().expect("attempt 5 of 6 should reschedule");
+        assert_eq!(job.attempts, 5);
+
+        assert!(
+            job.reschedule().is_none(),
+            "6th attempt should hit MAX_ATTEMPTS and be dropped"
+        );
+    }
+
+    #[test]
+    fn reschedule_caps_backoff_at_max_backoff_secs() {
+        let mut job = test_job();
+        for _ in 0..4 {
+            job = job.reschedule().expect("should still be under MAX_ATTEMPTS");
+        }
+        let before = now_secs();
+        let job = job.reschedule().expect("5th attempt should still reschedule");
+        assert!(job.next_visible_at <= before + MAX_BACKOFF_SECS);
+    }
+
+    #[tokio::test]
+    async fn in_memory_store_round_trips_a_job() {
+        let store = InMemoryJobStore::new();
+        store.enqueue(test_job()).await;
+        let due = store.take_due().await;
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].payload, json!({ "id": "interaction-1" }));
+    }
+
+    static TEST_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    fn test_queue_path() -> PathBuf {
+        let id = TEST_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir().join(format!(
+            "discord_bot_queue_test_{}_{}.ndjson",
+            std::process::id(),
+            id
+        ))
+    }
+
+    #[tokio::test]
+    async fn file_store_survives_reopening_from_the_same_path() {
+        let path = test_queue_path();
+
+        {
+            let store = FileJobStore::new(path.clone()).expect("should open a fresh queue file");
+            store.enqueue(test_job()).await;
+        }
+
+        let reopened = FileJobStore::new(path.clone()).expect("should reopen the persisted queue");
+        let due = reopened.take_due().await;
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].payload, json!({ "id": "interaction-1" }));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,69 @@
+//! Replay-protection cache for verified Discord signatures.
+//!
+//! The timestamp freshness check in `validate_signature` alone still lets an
+//! attacker replay a captured, still-fresh request as many times as they
+//! like within that window. This cache remembers signatures that have
+//! already been accepted and rejects a repeat until it ages out, evicting
+//! expired entries on each check so it stays bounded under load.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+pub struct ReplayCache {
+    window: Duration,
+    seen: Mutex<HashMap<String, Instant>>,
+}
+
+impl ReplayCache {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records `signature` and returns `true` if it hasn't been seen within
+    /// the freshness window; returns `false` (a replay) otherwise.
+    pub async fn check_and_record(&self, signature: &str) -> bool {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().await;
+        seen.retain(|_, inserted_at| now.duration_since(*inserted_at) < self.window);
+
+        if seen.contains_key(signature) {
+            return false;
+        }
+
+        seen.insert(signature.to_string(), now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn accepts_a_signature_once() {
+        let cache = ReplayCache::new(Duration::from_secs(60));
+        assert!(cache.check_and_record("sig-a").await);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_replay_within_the_window() {
+        let cache = ReplayCache::new(Duration::from_secs(60));
+        assert!(cache.check_and_record("sig-a").await);
+        assert!(!cache.check_and_record("sig-a").await);
+    }
+
+    #[tokio::test]
+    async fn accepts_again_once_the_entry_ages_out() {
+        let cache = ReplayCache::new(Duration::from_millis(20));
+        assert!(cache.check_and_record("sig-a").await);
+        assert!(!cache.check_and_record("sig-a").await);
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        assert!(cache.check_and_record("sig-a").await);
+    }
+}
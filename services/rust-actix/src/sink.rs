@@ -0,0 +1,240 @@
+//! Pluggable message-broker backends for publishing interaction payloads.
+//!
+//! The interaction-handling logic doesn't need to know whether a publish
+//! lands in Google Pub/Sub, Redis, a local file, or nowhere at all (tests);
+//! it only needs a [`MessageSink`]. The active implementation is selected via
+//! the `MESSAGE_SINK` environment variable (`pubsub`, `redis`, `file`, or
+//! `memory`), defaulting to `pubsub`.
+
+use crate::gcp_auth::ServiceAccountAuth;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// OAuth2 scope required to publish to Pub/Sub.
+pub const PUBSUB_SCOPE: &str = "https://www.googleapis.com/auth/pubsub";
+
+/// Error publishing to a sink's backing transport.
+#[derive(Debug)]
+pub struct SinkError(String);
+
+impl fmt::Display for SinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SinkError {}
+
+impl From<reqwest::Error> for SinkError {
+    fn from(e: reqwest::Error) -> Self {
+        SinkError(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for SinkError {
+    fn from(e: std::io::Error) -> Self {
+        SinkError(e.to_string())
+    }
+}
+
+impl From<redis::RedisError> for SinkError {
+    fn from(e: redis::RedisError) -> Self {
+        SinkError(e.to_string())
+    }
+}
+
+impl From<crate::gcp_auth::AuthError> for SinkError {
+    fn from(e: crate::gcp_auth::AuthError) -> Self {
+        SinkError(e.to_string())
+    }
+}
+
+/// A destination for sanitized interaction payloads.
+#[async_trait::async_trait]
+pub trait MessageSink: Send + Sync {
+    async fn publish(&self, payload: Value, attributes: Map<String, Value>) -> Result<(), SinkError>;
+}
+
+/// Publishes to Google Pub/Sub's REST API: the emulator over plain HTTP when
+/// `PUBSUB_EMULATOR_HOST` is set, otherwise production Pub/Sub over TLS
+/// authenticated with a service account's OAuth2 token. A no-op if
+/// project/topic aren't both configured, so deployments without Pub/Sub
+/// simply drain the queue instantly.
+pub struct PubSubSink {
+    http_client: reqwest::Client,
+    project_id: Option<String>,
+    topic: Option<String>,
+    emulator_host: Option<String>,
+    auth: Option<ServiceAccountAuth>,
+}
+
+impl PubSubSink {
+    pub fn new(
+        http_client: reqwest::Client,
+        project_id: Option<String>,
+        topic: Option<String>,
+        emulator_host: Option<String>,
+        auth: Option<ServiceAccountAuth>,
+    ) -> Self {
+        Self {
+            http_client,
+            project_id,
+            topic,
+            emulator_host,
+            auth,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageSink for PubSubSink {
+    async fn publish(&self, payload: Value, attributes: Map<String, Value>) -> Result<(), SinkError> {
+        let (project, topic) = match (&self.project_id, &self.topic) {
+            (Some(p), Some(t)) => (p, t),
+            _ => return Ok(()),
+        };
+
+        let json_str = serde_json::to_string(&payload).unwrap_or_default();
+        let base64_data = BASE64.encode(json_str.as_bytes());
+
+        let request_body = serde_json::json!({
+            "messages": [{
+                "data": base64_data,
+                "attributes": attributes,
+            }]
+        });
+
+        let mut request = match (&self.emulator_host, &self.auth) {
+            (Some(host), _) => {
+                let url = format!("http://{}/v1/projects/{}/topics/{}:publish", host, project, topic);
+                self.http_client.post(url)
+            }
+            (None, Some(auth)) => {
+                let url = format!(
+                    "https://pubsub.googleapis.com/v1/projects/{}/topics/{}:publish",
+                    project, topic
+                );
+                let token = auth.access_token().await?;
+                // Reuse the rustls-hardened client `ServiceAccountAuth` already
+                // built for the token exchange, rather than `self.http_client`,
+                // so production publish traffic gets the same TLS hardening.
+                auth.http_client()
+                    .post(url)
+                    .header("Authorization", format!("Bearer {}", token))
+            }
+            (None, None) => {
+                return Err(SinkError(
+                    "Pub/Sub is configured with a project/topic but neither PUBSUB_EMULATOR_HOST \
+                     nor a service account key (GOOGLE_APPLICATION_CREDENTIALS) is set"
+                        .to_string(),
+                ));
+            }
+        };
+        request = request.json(&request_body);
+
+        let resp = request.send().await?;
+        if resp.status().is_success() {
+            log::info!("Published to Pub/Sub successfully");
+            Ok(())
+        } else {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            Err(SinkError(format!("Pub/Sub publish failed: HTTP {} - {}", status, body)))
+        }
+    }
+}
+
+/// Drops nothing on the floor but sends nowhere either; used by the test
+/// suite to exercise the interaction pipeline without a real broker.
+#[derive(Default)]
+pub struct InMemorySink {
+    published: Mutex<Vec<(Value, Map<String, Value>)>>,
+}
+
+impl InMemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of everything published so far, for assertions in tests.
+    pub fn published(&self) -> Vec<(Value, Map<String, Value>)> {
+        self.published.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageSink for InMemorySink {
+    async fn publish(&self, payload: Value, attributes: Map<String, Value>) -> Result<(), SinkError> {
+        self.published.lock().unwrap().push((payload, attributes));
+        Ok(())
+    }
+}
+
+/// Appends each published message as an NDJSON line to a local file. Handy
+/// for local development or low-volume deployments without a broker.
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageSink for FileSink {
+    async fn publish(&self, payload: Value, attributes: Map<String, Value>) -> Result<(), SinkError> {
+        use std::io::Write;
+
+        let record = serde_json::json!({ "payload": payload, "attributes": attributes });
+        let line = serde_json::to_string(&record).unwrap_or_default();
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+/// Publishes to a Redis stream via `XADD`.
+pub struct RedisSink {
+    connection: redis::aio::ConnectionManager,
+    stream_key: String,
+}
+
+impl RedisSink {
+    pub async fn new(redis_url: &str, stream_key: String) -> Result<Self, SinkError> {
+        let client = redis::Client::open(redis_url)?;
+        let connection = client.get_connection_manager().await?;
+        Ok(Self {
+            connection,
+            stream_key,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageSink for RedisSink {
+    async fn publish(&self, payload: Value, attributes: Map<String, Value>) -> Result<(), SinkError> {
+        use redis::AsyncCommands;
+
+        let payload_str = serde_json::to_string(&payload).unwrap_or_default();
+        let attributes_str = serde_json::to_string(&attributes).unwrap_or_default();
+
+        let mut connection = self.connection.clone();
+        let _: String = connection
+            .xadd(
+                &self.stream_key,
+                "*",
+                &[("payload", payload_str.as_str()), ("attributes", attributes_str.as_str())],
+            )
+            .await?;
+        Ok(())
+    }
+}